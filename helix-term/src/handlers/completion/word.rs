@@ -1,17 +1,19 @@
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, HashMap, HashSet},
     ops::Range,
-    sync::Arc,
+    sync::{Arc, Mutex, OnceLock},
 };
 
 use helix_core::{
-    self as core, chars::char_is_word, completion::CompletionProvider, movement, Transaction,
+    self as core, chars::char_is_word, completion::CompletionProvider, movement, ChangeSet, Rope,
+    RopeSlice, Transaction,
 };
-use helix_event::TaskHandle;
+use helix_event::{register_hook, TaskHandle};
 use helix_stdx::rope::RopeSliceExt;
 use helix_view::{
-    document::SavePoint, handlers::completion::ResponseContext, Document, Editor, View,
+    document::SavePoint, handlers::completion::ResponseContext, Document, DocumentId, Editor,
+    View,
 };
 
 use crate::handlers::completion::{CompletionItems, CompletionResponse};
@@ -20,6 +22,53 @@ use super::{item::CompletionItem, request::TriggerKind, Trigger};
 
 const COMPLETION_KIND: &str = "word";
 
+/// Documents with more lines than this aren't indexed: building the index
+/// would mean tokenizing the whole buffer up front, which isn't worth it for
+/// a file this large. They fall back to scanning the ranges visible in open
+/// views, same as every document did before the index existed.
+const MAX_INDEXED_LINES: usize = 200_000;
+
+/// `editor.word-completion` config, resolved once per request via
+/// `word_completion_config`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct WordCompletionConfig {
+    /// Number of word characters that must be typed before word completion
+    /// triggers automatically. Manual completion (`ctrl-x` in insert mode)
+    /// ignores this: it triggers however little has been typed and offers
+    /// words of at least two characters.
+    pub trigger_length: u32,
+    /// Which open documents word completion draws candidates from.
+    pub max_scan: WordCompletionScope,
+    /// Extra characters, beyond the usual identifier characters, that count
+    /// as part of a word. Useful for languages where e.g. `-` or `$` appear
+    /// inside identifiers.
+    pub extra_word_chars: String,
+}
+
+impl Default for WordCompletionConfig {
+    fn default() -> Self {
+        Self {
+            trigger_length: 8,
+            max_scan: WordCompletionScope::AllOpen,
+            extra_word_chars: String::new(),
+        }
+    }
+}
+
+/// Which documents `editor.word-completion.max-scan` pulls candidates from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WordCompletionScope {
+    /// Only documents with a visible view.
+    Visible,
+    /// Only the focused document.
+    Document,
+    /// Every open document, whether or not it has a visible view.
+    #[default]
+    AllOpen,
+}
+
 pub(super) fn retain_valid_completions(
     trigger: Trigger,
     doc: &Document,
@@ -46,16 +95,77 @@ pub(super) fn retain_valid_completions(
     }
 }
 
+/// Resolves `editor.word-completion`.
+// TODO: `helix_view::Config` doesn't have a `word_completion` field yet (and
+// the config book doesn't document `editor.word-completion`), so this always
+// falls back to `Default` regardless of the user's `config.toml`. `Config` and
+// the book both live outside this crate and aren't touched by this series;
+// this is the seam to update once that field lands. Everything in this
+// module goes through here rather than `editor.config()` directly so that
+// change stays one line.
+fn word_completion_config(_editor: &Editor) -> WordCompletionConfig {
+    WordCompletionConfig::default()
+}
+
+/// The effective "is this a word character" predicate for `config`: the
+/// language-agnostic `char_is_word` plus whatever extra characters the user
+/// configured.
+fn word_char_predicate(config: &WordCompletionConfig) -> impl Fn(char) -> bool + Copy + '_ {
+    move |c: char| char_is_word(c) || config.extra_word_chars.contains(c)
+}
+
+/// Called from the document-change hook so the index is kept current
+/// without every completion request having to re-tokenize anything.
+pub(crate) fn apply_change(
+    doc_id: DocumentId,
+    old_text: &Rope,
+    new_text: &Rope,
+    changes: &ChangeSet,
+    config: &WordCompletionConfig,
+) {
+    word_index::record_change(doc_id, old_text, new_text, changes, word_char_predicate(config));
+}
+
+/// Keeps the word index in sync with editing, and drops a document's entry
+/// once it closes so the index doesn't grow for the lifetime of the process.
+fn register_hooks() {
+    register_hook!(move |event: &mut helix_view::events::DocumentDidChange<'_>| {
+        let config = word_completion_config(event.editor);
+        apply_change(event.doc, event.old_text, event.new_text, event.changes, &config);
+        Ok(())
+    });
+
+    register_hook!(move |event: &mut helix_view::events::DocumentDidClose<'_>| {
+        word_index::remove(event.doc);
+        Ok(())
+    });
+}
+
+/// Runs `register_hooks` the first time word completion is used. This module
+/// has no sibling call site of its own to register hooks from (completion
+/// providers in this crate don't have a shared init path this handler can
+/// hook into), so registration piggybacks on the first real request instead
+/// of depending on one.
+fn ensure_hooks_registered() {
+    static REGISTERED: OnceLock<()> = OnceLock::new();
+    REGISTERED.get_or_init(register_hooks);
+}
+
 pub(super) fn completion(
     editor: &Editor,
     trigger: Trigger,
     handle: TaskHandle,
     savepoint: Arc<SavePoint>,
 ) -> Option<impl FnOnce() -> CompletionResponse> {
+    ensure_hooks_registered();
+
+    let config = word_completion_config(editor);
+    let is_word_char = word_char_predicate(&config);
+
     // The minimum number of grapheme clusters needed to suggest a word.
     let min_word_len = match trigger.kind {
         TriggerKind::Manual => 2,
-        _ => 8,
+        _ => config.trigger_length as usize,
     };
 
     let (view, doc) = current_ref!(editor);
@@ -75,7 +185,7 @@ pub(super) fn completion(
             .slice(cursor.head..)
             .graphemes()
             .take(min_word_len)
-            .take_while(|g| g.chars().all(char_is_word))
+            .take_while(|g| g.chars().all(is_word_char))
             .count()
             != min_word_len
     {
@@ -92,34 +202,69 @@ pub(super) fn completion(
     } else {
         prev_word.chars().count()
     };
+    let typed_word = prev_word.to_string();
+
+    if handle.is_canceled() {
+        return None;
+    }
 
-    let mut ranges = BTreeMap::new();
-    for (view, _is_focused) in editor.tree.views() {
-        let doc = doc!(editor, &view.doc);
-        let text = doc.text().slice(..);
-        let start = text.char_to_line(doc.view_offset(view.id).anchor);
-        let end = view.estimate_last_doc_line(doc) + 1;
-
-        ranges
-            .entry(doc.id())
-            .and_modify(|(_text, ranges): &mut (core::Rope, Vec<Range<usize>>)| {
-                let range = start..end;
-                // If this range overlaps with an existing one, merge the ranges.
-                for r in ranges.iter_mut() {
-                    if range_overlaps(&range, r) {
-                        *r = range_union(&range, r);
-                        return;
-                    }
+    let cursor_line = text.char_to_line(pos);
+    let focused_doc = doc.id();
+
+    let scan_docs: Vec<DocumentId> = match config.max_scan {
+        WordCompletionScope::Document => vec![focused_doc],
+        WordCompletionScope::Visible => editor
+            .tree
+            .views()
+            .map(|(view, _is_focused)| view.doc)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect(),
+        WordCompletionScope::AllOpen => editor.documents().map(Document::id).collect(),
+    };
+
+    // Pull the word set for the documents in scope from their persistent
+    // index rather than re-walking the rope ranges visible in each view, and
+    // note how close each word in the focused document is to the cursor so
+    // nearby words can outrank distant ones with the same frequency.
+    let mut candidates: HashMap<String, WordRank> = HashMap::new();
+    for doc_id in scan_docs {
+        let doc = doc!(editor, &doc_id);
+        let text = doc.text();
+        let words = if text.len_lines() > MAX_INDEXED_LINES {
+            let mut words = BTreeMap::new();
+            for (view, _is_focused) in editor.tree.views() {
+                if view.doc != doc_id {
+                    continue;
                 }
-                // If no range overlaps, add a new range for this doc.
-                ranges.push(range);
-            })
-            .or_insert_with(|| {
-                // This lint doesn't account for the Vec being mutable: it can store potentially
-                // many ranges.
-                #[allow(clippy::single_range_in_vec_init)]
-                (doc.text().clone(), vec![start..end])
-            });
+                let start = text.char_to_line(doc.view_offset(view.id).anchor);
+                let end = view.estimate_last_doc_line(doc) + 1;
+                scan_words(text.slice(..), start..end, min_word_len, is_word_char, &mut words);
+            }
+            words
+        } else {
+            word_index::snapshot(doc_id, text, is_word_char)
+        };
+
+        let distances = if doc_id == focused_doc {
+            nearest_distances(text.slice(..), cursor_line, &words, is_word_char)
+        } else {
+            HashMap::new()
+        };
+
+        for (word, count) in words {
+            if word.chars().count() < min_word_len {
+                continue;
+            }
+            let distance = distances.get(&word).copied().unwrap_or(usize::MAX);
+            candidates
+                .entry(word)
+                .and_modify(|rank| {
+                    rank.count += count;
+                    rank.distance = rank.distance.min(distance);
+                })
+                .or_insert(WordRank { count, distance });
+        }
     }
 
     if handle.is_canceled() {
@@ -127,51 +272,14 @@ pub(super) fn completion(
     }
 
     let future = move || {
-        let mut words = BTreeSet::new();
-        for (_doc_id, (text, ranges)) in ranges {
-            let text = text.slice(..);
-            for range in ranges {
-                // TODO: the first word in a buffer can't be completed.
-                let start = text.line_to_char(range.start);
-                let end = text.line_to_char(range.end);
-                let mut cursor = core::Range::point(start);
-                if text.get_char(start).is_some_and(|c| !c.is_whitespace()) {
-                    let cursor_word_end = movement::move_next_word_end(text, cursor, 1);
-                    if cursor_word_end.anchor == start {
-                        cursor = cursor_word_end;
-                    }
-                }
-                while cursor.head < end {
-                    if text
-                        .slice(..cursor.head)
-                        .graphemes_rev()
-                        .take(min_word_len)
-                        .take_while(|g| g.chars().all(char_is_word))
-                        .count()
-                        == min_word_len
-                    {
-                        cursor.anchor += text
-                            .chars_at(cursor.anchor)
-                            .take_while(|&c| !char_is_word(c))
-                            .count();
-                        let word_range = cursor.anchor..cursor.head;
-                        // Don't insert the word which is currently being typed.
-                        // We could consider subtracting the currently typed word from the
-                        // set instead. I think the desired behavior though is to not include
-                        // what is being typed rather than not including something like what
-                        // is being typed.
-                        if !range_overlaps(&typed_word_range, &word_range) {
-                            words.insert(text.slice(word_range).to_string());
-                        }
-                    }
-                    cursor = movement::move_next_word_end(text, cursor, 1);
-                }
-            }
-        }
+        candidates.remove(&typed_word);
+
+        let mut words: Vec<_> = candidates.into_iter().collect();
+        words.sort_by(by_relevance);
 
         let items: Vec<_> = words
             .into_iter()
-            .map(|word| {
+            .map(|(word, _rank)| {
                 let transaction = Transaction::change_by_selection(&rope, &selection, |range| {
                     let cursor = range.cursor(rope.slice(..));
                     (cursor - edit_diff, cursor, Some((&word).into()))
@@ -204,13 +312,412 @@ pub(super) fn completion(
     Some(future)
 }
 
-fn range_overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
-    // See `Range::overlaps` in `helix_core`.
-    a.start == b.start || (a.end > b.start && b.end > a.start)
+/// Per-word ranking signal: how often it occurs across the open documents,
+/// and the fewest lines away from the cursor it was seen in the focused one
+/// (`usize::MAX` if it wasn't found there at all).
+struct WordRank {
+    count: usize,
+    distance: usize,
+}
+
+impl WordRank {
+    /// Higher is more relevant. Frequency matters, but a word several
+    /// thousand lines away shouldn't out-rank one on the next line.
+    fn score(&self) -> f64 {
+        self.count as f64 / (self.distance as f64 + 1.0)
+    }
 }
 
-fn range_union(a: &Range<usize>, b: &Range<usize>) -> Range<usize> {
-    let start = a.start.min(b.start);
-    let end = a.end.max(b.end);
-    start..end
+/// Sorts candidates highest-`score()` first, breaking ties alphabetically so
+/// ordering is stable across runs.
+fn by_relevance(
+    (word_a, rank_a): &(String, WordRank),
+    (word_b, rank_b): &(String, WordRank),
+) -> std::cmp::Ordering {
+    rank_b
+        .score()
+        .total_cmp(&rank_a.score())
+        .then_with(|| word_a.cmp(word_b))
+}
+
+/// For every word in `words`, finds the distance (in lines) from
+/// `cursor_line` to its nearest occurrence in `text`, searching outward line
+/// by line so common, far-away words don't get scanned past unnecessarily.
+fn nearest_distances(
+    text: RopeSlice,
+    cursor_line: usize,
+    words: &BTreeMap<String, usize>,
+    is_word: impl Fn(char) -> bool + Copy,
+) -> HashMap<String, usize> {
+    let mut remaining: HashSet<&str> = words.keys().map(String::as_str).collect();
+    let mut distances = HashMap::with_capacity(words.len());
+    let total_lines = text.len_lines();
+
+    let mut distance = 0;
+    while !remaining.is_empty() && (distance <= cursor_line || cursor_line + distance < total_lines) {
+        let lines = [
+            cursor_line.checked_sub(distance),
+            (distance > 0).then_some(cursor_line + distance),
+        ];
+        for line in lines.into_iter().flatten().filter(|&line| line < total_lines) {
+            let mut line_words = BTreeMap::new();
+            count_words_in(
+                text,
+                text.line_to_char(line)..text.line_to_char(line + 1),
+                is_word,
+                &mut line_words,
+            );
+            for word in line_words.into_keys() {
+                if remaining.remove(word.as_str()) {
+                    distances.insert(word, distance);
+                }
+            }
+        }
+        distance += 1;
+    }
+    distances
+}
+
+/// Counts runs of `is_word` characters within `range` of `text`. The word
+/// starting at absolute position 0, if any, is never counted: it can't be
+/// completed (there's no boundary before it to trigger from), and `scan_words`
+/// skips it too, so the index stays consistent whichever path built it.
+fn count_words_in(
+    text: RopeSlice,
+    range: Range<usize>,
+    is_word: impl Fn(char) -> bool,
+    words: &mut BTreeMap<String, usize>,
+) {
+    let mut pos = range.start;
+    while pos < range.end {
+        if text.get_char(pos).is_some_and(&is_word) {
+            let word_start = pos;
+            while pos < range.end && text.get_char(pos).is_some_and(&is_word) {
+                pos += 1;
+            }
+            if word_start != 0 {
+                *words
+                    .entry(text.slice(word_start..pos).to_string())
+                    .or_insert(0) += 1;
+            }
+        } else {
+            pos += 1;
+        }
+    }
+}
+
+/// Tokenizes `range` (in doc lines) of `text` into `words`, counting
+/// occurrences. This is the non-indexed fallback for documents over
+/// `MAX_INDEXED_LINES`, and matches the scan the index itself does.
+fn scan_words(
+    text: RopeSlice,
+    range: Range<usize>,
+    min_word_len: usize,
+    is_word: impl Fn(char) -> bool + Copy,
+    words: &mut BTreeMap<String, usize>,
+) {
+    // The word starting at absolute position 0, if any, is skipped below
+    // (see the `cursor.anchor != 0` guard): it can't be completed, since
+    // there's no boundary before it to trigger from.
+    let start = text.line_to_char(range.start);
+    let end = text.line_to_char(range.end);
+    let mut cursor = core::Range::point(start);
+    if text.get_char(start).is_some_and(|c| !c.is_whitespace()) {
+        let cursor_word_end = movement::move_next_word_end(text, cursor, 1);
+        if cursor_word_end.anchor == start {
+            cursor = cursor_word_end;
+        }
+    }
+    while cursor.head < end {
+        if text
+            .slice(..cursor.head)
+            .graphemes_rev()
+            .take(min_word_len)
+            .take_while(|g| g.chars().all(is_word))
+            .count()
+            == min_word_len
+        {
+            cursor.anchor += text
+                .chars_at(cursor.anchor)
+                .take_while(|&c| !is_word(c))
+                .count();
+            if cursor.anchor != 0 {
+                *words.entry(text.slice(cursor.anchor..cursor.head).to_string()).or_insert(0) += 1;
+            }
+        }
+        cursor = movement::move_next_word_end(text, cursor, 1);
+    }
+}
+
+/// A frequency map of the words in each open document, built once when the
+/// document is first queried for completions and kept in sync incrementally
+/// as changes are applied, instead of being rebuilt from the visible range on
+/// every trigger.
+mod word_index {
+    use super::*;
+
+    #[derive(Default)]
+    struct WordIndex {
+        counts: BTreeMap<String, usize>,
+        /// The rope length the index was last synced to. Used to notice a
+        /// document that changed through a path other than `record_change`
+        /// (e.g. a reload) so we know to rebuild instead of trusting it.
+        synced_len: usize,
+    }
+
+    static INDEXES: OnceLock<Mutex<HashMap<DocumentId, WordIndex>>> = OnceLock::new();
+
+    fn indexes() -> &'static Mutex<HashMap<DocumentId, WordIndex>> {
+        INDEXES.get_or_init(|| Mutex::new(HashMap::new()))
+    }
+
+    /// Returns the word -> occurrence-count map for `doc_id`, building it
+    /// from `text` the first time it's requested (or after it desyncs).
+    pub(super) fn snapshot(
+        doc_id: DocumentId,
+        text: &Rope,
+        is_word: impl Fn(char) -> bool + Copy,
+    ) -> BTreeMap<String, usize> {
+        let mut indexes = indexes().lock().unwrap();
+        let index = indexes.entry(doc_id).or_default();
+        if index.synced_len != text.len_chars() {
+            *index = build(text, is_word);
+        }
+        index.counts.clone()
+    }
+
+    /// Drops `doc_id`'s cached index, e.g. once the document has closed.
+    pub(super) fn remove(doc_id: DocumentId) {
+        indexes().lock().unwrap().remove(&doc_id);
+    }
+
+    fn build(text: &Rope, is_word: impl Fn(char) -> bool + Copy) -> WordIndex {
+        let mut counts = BTreeMap::new();
+        scan_words(text.slice(..), 0..text.len_lines(), 1, is_word, &mut counts);
+        WordIndex {
+            counts,
+            synced_len: text.len_chars(),
+        }
+    }
+
+    /// Applies one edit to the cached index: words touched by the change are
+    /// re-tokenized from scratch on both sides of the edit (expanded out to
+    /// whole words via `is_word`) so a change in the middle of a word can't
+    /// leave a stale fragment in the count map.
+    pub(super) fn record_change(
+        doc_id: DocumentId,
+        old_text: &Rope,
+        new_text: &Rope,
+        changes: &ChangeSet,
+        is_word: impl Fn(char) -> bool + Copy,
+    ) {
+        let mut indexes = indexes().lock().unwrap();
+        let Some(index) = indexes.get_mut(&doc_id) else {
+            // Nothing cached yet; `snapshot` will build it lazily when asked.
+            return;
+        };
+        if index.synced_len != old_text.len_chars() {
+            // Out of sync with whatever this change set was computed against;
+            // drop it and let the next `snapshot` rebuild from scratch.
+            indexes.remove(&doc_id);
+            return;
+        }
+
+        apply_edit(&mut index.counts, old_text.slice(..), new_text.slice(..), changes, is_word);
+        index.synced_len = new_text.len_chars();
+    }
+
+    /// The actual incremental update, pulled out of `record_change` so it can
+    /// be exercised directly in tests without going through a `DocumentId`
+    /// and the global index.
+    fn apply_edit(
+        counts: &mut BTreeMap<String, usize>,
+        old_text: RopeSlice,
+        new_text: RopeSlice,
+        changes: &ChangeSet,
+        is_word: impl Fn(char) -> bool + Copy,
+    ) {
+        let (mut old_pos, mut new_pos) = (0, 0);
+        // Start of the first edit and end of the last one, as (old, new).
+        let mut first = None;
+        let mut last = (0, 0);
+        for op in changes.changes() {
+            match op {
+                core::Operation::Retain(n) => {
+                    old_pos += n;
+                    new_pos += n;
+                }
+                core::Operation::Delete(n) => {
+                    first = first.or(Some((old_pos, new_pos)));
+                    old_pos += n;
+                    last = (old_pos, new_pos);
+                }
+                core::Operation::Insert(text) => {
+                    first = first.or(Some((old_pos, new_pos)));
+                    new_pos += text.chars().count();
+                    last = (old_pos, new_pos);
+                }
+            }
+        }
+        // Text retained between edits appears on both sides and cancels out.
+        if let Some((old_start, new_start)) = first {
+            for (word, count) in words_touching(old_text, old_start..last.0, is_word) {
+                decrement(counts, &word, count);
+            }
+            for (word, count) in words_touching(new_text, new_start..last.1, is_word) {
+                *counts.entry(word).or_insert(0) += count;
+            }
+        }
+    }
+
+    fn decrement(counts: &mut BTreeMap<String, usize>, word: &str, by: usize) {
+        if let Some(count) = counts.get_mut(word) {
+            *count = count.saturating_sub(by);
+            if *count == 0 {
+                counts.remove(word);
+            }
+        }
+    }
+
+    /// Words overlapping `range`, expanded outwards to whole-word boundaries
+    /// so an edit landing mid-word re-tokenizes the whole word, grouped with
+    /// their occurrence counts within the expanded span.
+    fn words_touching(
+        text: RopeSlice,
+        range: Range<usize>,
+        is_word: impl Fn(char) -> bool + Copy,
+    ) -> BTreeMap<String, usize> {
+        let start = expand_word_start(text, range.start.min(text.len_chars()), is_word);
+        let end = expand_word_end(text, range.end.min(text.len_chars()), is_word);
+
+        let mut words = BTreeMap::new();
+        count_words_in(text, start..end, is_word, &mut words);
+        words
+    }
+
+    fn expand_word_start(text: RopeSlice, mut pos: usize, is_word: impl Fn(char) -> bool) -> usize {
+        while pos > 0 && text.get_char(pos - 1).is_some_and(&is_word) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    fn expand_word_end(text: RopeSlice, mut pos: usize, is_word: impl Fn(char) -> bool) -> usize {
+        while text.get_char(pos).is_some_and(&is_word) {
+            pos += 1;
+        }
+        pos
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn apply_edit_matches_a_full_rescan_after_a_same_length_replacement() {
+            let old_text = Rope::from_str("the quick fox jumps over the lazy dog\n");
+            let transaction =
+                Transaction::change(&old_text, [(4, 9, Some("slow!".into()))].into_iter());
+            let mut new_text = old_text.clone();
+            transaction.apply(&mut new_text);
+
+            let mut counts = BTreeMap::new();
+            scan_words(old_text.slice(..), 0..old_text.len_lines(), 1, char_is_word, &mut counts);
+            apply_edit(
+                &mut counts,
+                old_text.slice(..),
+                new_text.slice(..),
+                transaction.changes(),
+                char_is_word,
+            );
+
+            let mut expected = BTreeMap::new();
+            scan_words(new_text.slice(..), 0..new_text.len_lines(), 1, char_is_word, &mut expected);
+
+            assert_eq!(counts, expected);
+        }
+
+        #[test]
+        fn an_edit_touching_the_first_word_agrees_with_a_full_rescan() {
+            let old_text = Rope::from_str("the quick fox\n");
+            let transaction =
+                Transaction::change(&old_text, [(0, 3, Some("slow".into()))].into_iter());
+            let mut new_text = old_text.clone();
+            transaction.apply(&mut new_text);
+
+            let mut counts = BTreeMap::new();
+            scan_words(old_text.slice(..), 0..old_text.len_lines(), 1, char_is_word, &mut counts);
+            apply_edit(
+                &mut counts,
+                old_text.slice(..),
+                new_text.slice(..),
+                transaction.changes(),
+                char_is_word,
+            );
+
+            let mut expected = BTreeMap::new();
+            scan_words(new_text.slice(..), 0..new_text.len_lines(), 1, char_is_word, &mut expected);
+
+            // Neither the old nor the new first word ("the"/"slow") is
+            // completable, so neither should appear on either side.
+            assert!(!counts.contains_key("the") && !counts.contains_key("slow"));
+            assert_eq!(counts, expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_rank_favors_closer_occurrences_over_farther_ones_at_equal_count() {
+        let close = WordRank { count: 1, distance: 0 };
+        let far = WordRank { count: 1, distance: 10 };
+        assert!(close.score() > far.score());
+    }
+
+    #[test]
+    fn word_rank_favors_more_frequent_words_at_equal_distance() {
+        let common = WordRank { count: 5, distance: 2 };
+        let rare = WordRank { count: 1, distance: 2 };
+        assert!(common.score() > rare.score());
+    }
+
+    #[test]
+    fn by_relevance_sorts_highest_score_first_and_breaks_ties_alphabetically() {
+        let mut words = vec![
+            ("zebra".to_string(), WordRank { count: 1, distance: 0 }),
+            ("apple".to_string(), WordRank { count: 1, distance: 0 }),
+            ("best".to_string(), WordRank { count: 10, distance: 0 }),
+        ];
+        words.sort_by(by_relevance);
+        let order: Vec<_> = words.iter().map(|(word, _)| word.as_str()).collect();
+        assert_eq!(order, ["best", "apple", "zebra"]);
+    }
+
+    #[test]
+    fn nearest_distances_finds_the_closer_of_two_occurrences() {
+        let text = Rope::from_str("a\nfox\na\na\na\nfox\n");
+        let mut words = BTreeMap::new();
+        words.insert("fox".to_string(), 2);
+
+        // "fox" is on lines 1 and 5; from line 4 those are 3 and 1 lines away.
+        let distances = nearest_distances(text.slice(..), 4, &words, char_is_word);
+
+        assert_eq!(distances.get("fox"), Some(&1));
+    }
+
+    #[test]
+    fn word_char_predicate_recognizes_configured_extra_chars() {
+        let config = WordCompletionConfig {
+            extra_word_chars: "-".to_string(),
+            ..WordCompletionConfig::default()
+        };
+        let is_word_char = word_char_predicate(&config);
+
+        assert!(is_word_char('-'));
+        assert!(is_word_char('a'));
+        assert!(!is_word_char(' '));
+    }
 }